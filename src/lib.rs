@@ -115,6 +115,18 @@
 //! }
 //! ```
 //!
+//! The crate also provides this `StreamingIterator` trait ready to use, together with the lazy
+//! adapters [`map`][StreamingIterator::map] and [`filter`][StreamingIterator::filter] and the
+//! consuming [`fold`][StreamingIterator::fold] and [`for_each`][StreamingIterator::for_each], so that user
+//! code does not have to redefine it from scratch as above. [`StreamingIterator::to_iter`] and
+//! [`from_iter`] bridge to and from [`core::iter::Iterator`] for the case where items do not
+//! actually borrow from the streaming iterator.
+//!
+//! For combining several streams of possibly different concrete types in sorted order, e.g. to
+//! take the union, intersection or difference of several sorted streams, see [`union`],
+//! [`intersection`], [`difference`] and the [`Peekable`] wrapper they are built on. These require
+//! an allocator, which this crate pulls in via `extern crate alloc`.
+//!
 //! ## Lifetime Elision
 //!
 //! Lifetime abstractions support elision of lifetimes. The placeholder lifetime will be assigned to
@@ -130,6 +142,23 @@
 //! type Expanded<'outer> = Lt!(for<'a> &'a [&'outer str]);
 //! ```
 //!
+//! ## Multiple Lifetimes
+//!
+//! Some associated types emulating GATs need more than one lifetime parameter, e.g. a cursor
+//! whose item borrows both a buffer and a separately borrowed index. [`Lt2!`] and [`Lt3!`]
+//! together with [`LtAbs2`]/[`LtApply2`] and [`LtAbs3`]/[`LtApply3`] work like [`Lt!`],
+//! [`LtAbs`] and [`LtApply`], but bind two or three placeholder lifetimes instead of one. Unlike
+//! [`Lt!`], they do not support lifetime elision, so the placeholder lifetimes have to be named:
+//!
+//! ```rust
+//! # use lifetime_abstractions::*;
+//! type TwoLifetimes = Lt2!(for<'a, 'b> (&'a str, &'b [u8]));
+//!
+//! fn borrow_two<'a, 'b>(s: &'a str, b: &'b [u8]) -> LtApply2<'a, 'b, TwoLifetimes> {
+//!     (s, b)
+//! }
+//! ```
+//!
 //! ## Implementation
 //!
 //! Lifetime abstractions `Lt!(for<'a> Something<'a>)` are represented using [function pointer
@@ -190,10 +219,16 @@
 //! additional type hints in places where I would expect type inference to be sufficient. This
 //! happens quite often when closure types interact with lifetime abstractions and may require [this
 //! technique to add a sufficiently generic type hint to a closure][constrain-closure].
+//! [`constrain_lt_fn`] and the [`lt_closure!`] macro built on it package this technique up so it
+//! does not have to be rewritten by hand every time it is needed.
 //!
 //! [streaming-iterator-article]:http://lukaskalbertodt.github.io/2018/08/03/solving-the-generalized-streaming-iterator-problem-without-gats.html
 //! [constrain-closure]:https://stackoverflow.com/a/46198877
 
+extern crate alloc;
+
+use alloc::{boxed::Box, collections::BinaryHeap, vec::Vec};
+use core::cmp::Ordering;
 use core::marker::PhantomData;
 
 /// Helper traits for type-level application of closures (and function pointers).
@@ -237,6 +272,78 @@ pub mod fn_helpers {
         T: FnOutput1<Arg> + FnOnce(Arg) -> <Self as FnOutput1<Arg>>::FnOutput1
     {
     }
+
+    /// Helper trait used to recover the output type of a 2-argument closure given only the input
+    /// types.
+    ///
+    /// For convenience, you can access the output type using the [`Apply2`] type alias.
+    ///
+    /// See [`FnOutput1`] for the 1-argument version, of which this is the direct analog.
+    pub trait FnOutput2<A, B> {
+        /// The output type returned by the closure.
+        type FnOutput2;
+    }
+
+    /// The output type returned when calling the 2-argument closure `Fn2` with arguments of type
+    /// `A` and `B`.
+    pub type Apply2<Fn2, A, B> = <Fn2 as FnOutput2<A, B>>::FnOutput2;
+
+    impl<T, A, B, Output> FnOutput2<A, B> for T
+    where
+        T: FnOnce(A, B) -> Output,
+    {
+        type FnOutput2 = T::Output;
+    }
+
+    /// Trait of 2-argument closures with an unconstrained output type.
+    ///
+    /// This has [`FnOutput2`] and [`FnOnce`] as supertypes. It uses [`FnOutput2`] to recover the
+    /// output required for the [`FnOnce`] bound on stable Rust.
+    pub trait FnBound2<A, B>:
+        FnOutput2<A, B> + FnOnce(A, B) -> <Self as FnOutput2<A, B>>::FnOutput2
+    {
+    }
+
+    impl<T, A, B> FnBound2<A, B> for T where
+        T: FnOutput2<A, B> + FnOnce(A, B) -> <Self as FnOutput2<A, B>>::FnOutput2
+    {
+    }
+
+    /// Helper trait used to recover the output type of a 3-argument closure given only the input
+    /// types.
+    ///
+    /// For convenience, you can access the output type using the [`Apply3`] type alias.
+    ///
+    /// See [`FnOutput1`] for the 1-argument version, of which this is the direct analog.
+    pub trait FnOutput3<A, B, C> {
+        /// The output type returned by the closure.
+        type FnOutput3;
+    }
+
+    /// The output type returned when calling the 3-argument closure `Fn3` with arguments of type
+    /// `A`, `B` and `C`.
+    pub type Apply3<Fn3, A, B, C> = <Fn3 as FnOutput3<A, B, C>>::FnOutput3;
+
+    impl<T, A, B, C, Output> FnOutput3<A, B, C> for T
+    where
+        T: FnOnce(A, B, C) -> Output,
+    {
+        type FnOutput3 = T::Output;
+    }
+
+    /// Trait of 3-argument closures with an unconstrained output type.
+    ///
+    /// This has [`FnOutput3`] and [`FnOnce`] as supertypes. It uses [`FnOutput3`] to recover the
+    /// output required for the [`FnOnce`] bound on stable Rust.
+    pub trait FnBound3<A, B, C>:
+        FnOutput3<A, B, C> + FnOnce(A, B, C) -> <Self as FnOutput3<A, B, C>>::FnOutput3
+    {
+    }
+
+    impl<T, A, B, C> FnBound3<A, B, C> for T where
+        T: FnOutput3<A, B, C> + FnOnce(A, B, C) -> <Self as FnOutput3<A, B, C>>::FnOutput3
+    {
+    }
 }
 
 use fn_helpers::*;
@@ -264,3 +371,790 @@ macro_rules! Lt {
     (for<$lt:lifetime> $ty:ty) => { for<$lt> fn($crate::Lt<$lt>) -> $ty };
     ($ty:ty) => { fn($crate::Lt) -> $ty };
 }
+
+/// A lifetime abstraction parameterized by two bound placeholder lifetimes.
+///
+/// This is the two-lifetime analog of [`LtAbs`], used for associated types emulating GATs that
+/// take two lifetime parameters, such as `type Item<'a, 'b>`.
+pub trait LtAbs2: for<'a, 'b> FnOutput2<Lt<'a>, Lt<'b>> {}
+
+impl<T> LtAbs2 for T where T: for<'a, 'b> FnOutput2<Lt<'a>, Lt<'b>> {}
+
+/// Substitutes two concrete lifetimes for the bound lifetimes in a two-lifetime abstraction.
+pub type LtApply2<'a, 'b, Abs> = Apply2<Abs, Lt<'a>, Lt<'b>>;
+
+/// Creates a two-lifetime abstraction, binding two placeholder lifetimes.
+///
+/// This allows writing `Lt2!(for<'a, 'b> SomeType<&'a str, &'b [u8]>)` which will expand to
+/// `for<'a, 'b> fn(Lt<'a>, Lt<'b>) -> SomeType<&'a str, &'b [u8]>`. Unlike [`Lt!`], it does not
+/// support lifetime elision: `Lt2!(SomeType)` expands to `fn(Lt, Lt) -> SomeType`, and since an
+/// elided lifetime in `SomeType` could refer to either placeholder, this only compiles when
+/// `SomeType` contains no elided lifetimes.
+#[macro_export]
+macro_rules! Lt2 {
+    (for<$lt1:lifetime, $lt2:lifetime> $ty:ty) => {
+        for<$lt1, $lt2> fn($crate::Lt<$lt1>, $crate::Lt<$lt2>) -> $ty
+    };
+    ($ty:ty) => { fn($crate::Lt, $crate::Lt) -> $ty };
+}
+
+/// A lifetime abstraction parameterized by three bound placeholder lifetimes.
+///
+/// This is the three-lifetime analog of [`LtAbs`].
+pub trait LtAbs3: for<'a, 'b, 'c> FnOutput3<Lt<'a>, Lt<'b>, Lt<'c>> {}
+
+impl<T> LtAbs3 for T where T: for<'a, 'b, 'c> FnOutput3<Lt<'a>, Lt<'b>, Lt<'c>> {}
+
+/// Substitutes three concrete lifetimes for the bound lifetimes in a three-lifetime abstraction.
+pub type LtApply3<'a, 'b, 'c, Abs> = Apply3<Abs, Lt<'a>, Lt<'b>, Lt<'c>>;
+
+/// Creates a three-lifetime abstraction, binding three placeholder lifetimes.
+///
+/// See [`Lt2!`] for the two-lifetime version, of which this is the direct analog.
+#[macro_export]
+macro_rules! Lt3 {
+    (for<$lt1:lifetime, $lt2:lifetime, $lt3:lifetime> $ty:ty) => {
+        for<$lt1, $lt2, $lt3> fn($crate::Lt<$lt1>, $crate::Lt<$lt2>, $crate::Lt<$lt3>) -> $ty
+    };
+    ($ty:ty) => { fn($crate::Lt, $crate::Lt, $crate::Lt) -> $ty };
+}
+
+/// An iterator that may yield items borrowing from the iterator itself.
+///
+/// Unlike [`Iterator`], whose `Item` cannot borrow from `&mut self`, `StreamingIterator::Item` is
+/// a [`LtAbs`], substituted with the lifetime of each `next` call. This mirrors the `Streamer`
+/// trait used throughout the `fst` crate to compose range/regex/union streams of different
+/// concrete types.
+///
+/// Use [`map`][Self::map], [`filter`][Self::filter], [`fold`][Self::fold] and
+/// [`for_each`][Self::for_each] to compose streaming iterators, analogous to the methods on
+/// [`Iterator`].
+pub trait StreamingIterator {
+    /// The, possibly borrowing, item abstraction yielded by [`next`][Self::next].
+    type Item: LtAbs;
+
+    /// Advances the streaming iterator and returns the next item, or `None` once exhausted.
+    fn next<'a>(&'a mut self) -> Option<LtApply<'a, Self::Item>>;
+
+    /// Returns a streaming iterator that yields the result of applying `f` to each item.
+    ///
+    /// Since the output item may itself borrow from the input item, `f`'s bound has to be
+    /// expressed as a higher-ranked closure over the placeholder lifetime, which type inference
+    /// usually cannot find on its own without an explicit type annotation. `f` additionally takes
+    /// an ignored [`Lt<'a>`][struct@Lt] as its first argument: without it, the placeholder lifetime
+    /// would only appear in the closure's return type, which rustc currently rejects (it requires
+    /// a higher-ranked lifetime to be "constrained" by an argument type). [`constrain_lt_fn`] and
+    /// [`lt_closure!`] turn this same shape into a reusable helper instead of writing it out by
+    /// hand at every call site.
+    fn map<OutItem, F>(self, f: F) -> Map<Self, OutItem, F>
+    where
+        Self: Sized,
+        OutItem: LtAbs,
+        F: for<'a> FnMut(Lt<'a>, LtApply<'a, Self::Item>) -> LtApply<'a, OutItem>,
+    {
+        Map {
+            s: self,
+            f,
+            out_item: PhantomData,
+        }
+    }
+
+    /// Returns a streaming iterator that only yields items for which `f` returns `true`.
+    ///
+    /// ```rust
+    /// # use lifetime_abstractions::*;
+    /// struct Words<'s> {
+    ///     rest: &'s str,
+    /// }
+    ///
+    /// impl<'s> StreamingIterator for Words<'s> {
+    ///     type Item = Lt!(for<'a> &'a str);
+    ///
+    ///     fn next<'a>(&'a mut self) -> Option<&'a str> {
+    ///         self.rest = self.rest.trim_start();
+    ///         if self.rest.is_empty() {
+    ///             return None;
+    ///         }
+    ///         let end = self.rest.find(' ').unwrap_or(self.rest.len());
+    ///         let (word, rest) = self.rest.split_at(end);
+    ///         self.rest = rest;
+    ///         Some(word)
+    ///     }
+    /// }
+    ///
+    /// let mut long_words = Words { rest: "a bb ccc dddd e" }.filter(|w: &&str| w.len() > 1);
+    /// let mut collected = Vec::new();
+    /// while let Some(w) = long_words.next() {
+    ///     collected.push(w.to_string());
+    /// }
+    /// assert_eq!(collected, vec!["bb", "ccc", "dddd"]);
+    /// ```
+    fn filter<F>(self, f: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: for<'a> FnMut(&LtApply<'a, Self::Item>) -> bool,
+    {
+        Filter { s: self, f }
+    }
+
+    /// Folds every item into an accumulator by applying `f`, returning the final accumulator.
+    fn fold<Acc, F>(mut self, init: Acc, mut f: F) -> Acc
+    where
+        Self: Sized,
+        F: for<'a> FnMut(Acc, LtApply<'a, Self::Item>) -> Acc,
+    {
+        let mut acc = init;
+        while let Some(item) = self.next() {
+            acc = f(acc, item);
+        }
+        acc
+    }
+
+    /// Calls `f` on each item for its side effects, draining the streaming iterator.
+    fn for_each<F>(mut self, mut f: F)
+    where
+        Self: Sized,
+        F: for<'a> FnMut(LtApply<'a, Self::Item>),
+    {
+        while let Some(item) = self.next() {
+            f(item);
+        }
+    }
+
+    /// Converts this streaming iterator into a plain [`Iterator`].
+    ///
+    /// This is only available when `Item` does not actually borrow from `self`, as witnessed by
+    /// the [`LtAbsStatic`] bound.
+    fn to_iter(self) -> ToIter<Self>
+    where
+        Self: Sized,
+        Self::Item: LtAbsStatic,
+    {
+        ToIter(self)
+    }
+}
+
+/// A lifetime abstraction that does not actually depend on its bound lifetime.
+///
+/// Rust has no direct way to state "this abstraction is lifetime-independent", so this trait has
+/// to be implemented explicitly, providing the `Owned` type equal to `LtApply<'a, Self>` for every
+/// `'a`. A blanket implementation is provided for `Lt!(for<'a> T)` where `T: 'static`, i.e. for
+/// abstractions that do not mention the placeholder lifetime at all.
+pub trait LtAbsStatic: LtAbs + for<'a> FnOutput1<Lt<'a>, FnOutput1 = Self::Owned> {
+    /// The type `T` such that `LtApply<'a, Self> = T` for every lifetime `'a`.
+    type Owned;
+}
+
+impl<T: 'static> LtAbsStatic for Lt!(for<'a> T) {
+    type Owned = T;
+}
+
+/// A plain [`Iterator`] adapter for a [`StreamingIterator`] whose items do not borrow from it.
+///
+/// Created by [`StreamingIterator::to_iter`].
+pub struct ToIter<S>(S);
+
+impl<S> Iterator for ToIter<S>
+where
+    S: StreamingIterator,
+    S::Item: LtAbsStatic,
+{
+    type Item = <S::Item as LtAbsStatic>::Owned;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// A [`StreamingIterator`] lifting a plain [`Iterator`] `I`.
+///
+/// Created by [`from_iter`].
+pub struct FromIter<I>(I);
+
+/// Lifts an [`Iterator`] into a [`StreamingIterator`] whose item abstraction never actually
+/// borrows from the iterator.
+pub fn from_iter<I: Iterator>(iter: I) -> FromIter<I> {
+    FromIter(iter)
+}
+
+impl<I: Iterator> StreamingIterator for FromIter<I> {
+    type Item = Lt!(for<'a> I::Item);
+
+    fn next<'a>(&'a mut self) -> Option<LtApply<'a, Self::Item>> {
+        self.0.next()
+    }
+}
+
+/// A [`StreamingIterator`] that maps each item of `S` using `F`.
+///
+/// Created by [`StreamingIterator::map`].
+pub struct Map<S, OutItem, F> {
+    s: S,
+    f: F,
+    out_item: PhantomData<OutItem>,
+}
+
+impl<S, OutItem, F> StreamingIterator for Map<S, OutItem, F>
+where
+    S: StreamingIterator,
+    OutItem: LtAbs,
+    F: for<'a> FnMut(Lt<'a>, LtApply<'a, S::Item>) -> LtApply<'a, OutItem>,
+{
+    type Item = OutItem;
+
+    fn next<'a>(&'a mut self) -> Option<LtApply<'a, Self::Item>> {
+        match self.s.next() {
+            Some(item) => Some((self.f)(Lt(PhantomData), item)),
+            None => None,
+        }
+    }
+}
+
+/// A [`StreamingIterator`] that only yields the items of `S` for which `F` returns `true`.
+///
+/// Created by [`StreamingIterator::filter`].
+pub struct Filter<S, F> {
+    s: S,
+    f: F,
+}
+
+impl<S, F> StreamingIterator for Filter<S, F>
+where
+    S: StreamingIterator,
+    F: for<'a> FnMut(&LtApply<'a, S::Item>) -> bool,
+{
+    type Item = S::Item;
+
+    fn next<'a>(&'a mut self) -> Option<LtApply<'a, Self::Item>> {
+        loop {
+            // SAFETY: The borrow checker ties every call to `s.next()` below to the lifetime of
+            // this whole function, `'a`, as required to be able to return an item borrowed from
+            // it. This conservatively forbids retrying the pull in a loop, even though at most one
+            // of the borrows is actually alive at a time: we stop looping the moment we get an
+            // item we return. Reborrowing through a raw pointer sidesteps this over-approximation
+            // without creating any actually-overlapping mutable borrows of `self.s`.
+            let s: &'a mut S = unsafe { &mut *(&mut self.s as *mut S) };
+            match s.next() {
+                Some(item) => {
+                    if (self.f)(&item) {
+                        return Some(item);
+                    }
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<S> StreamingIterator for Box<S>
+where
+    S: StreamingIterator + ?Sized,
+{
+    type Item = S::Item;
+
+    fn next<'a>(&'a mut self) -> Option<LtApply<'a, Self::Item>> {
+        (**self).next()
+    }
+}
+
+/// A [`StreamingIterator`] wrapper that caches an owned key extracted from the upcoming item.
+///
+/// Since [`StreamingIterator::next`] borrows `&mut self`, an item cannot be peeked and then
+/// retrieved later: the borrow returned by one call to `next` must end before the next call.
+/// `Peekable` works around this by pulling the next item when it is first peeked or popped and
+/// reducing it to an owned key `K` via a user-supplied callback, which is cached until
+/// [`pop`][Self::pop] consumes it. This is
+/// what [`union`], [`intersection`] and [`difference`] use to compare the heads of several streams
+/// of possibly different concrete types without holding a borrow from any of them.
+pub struct Peekable<S, K, F> {
+    s: S,
+    f: F,
+    cache: Option<Option<K>>,
+}
+
+impl<S, K, F> Peekable<S, K, F>
+where
+    S: StreamingIterator,
+    F: for<'a> FnMut(&LtApply<'a, S::Item>) -> K,
+{
+    /// Wraps `s`, using `f` to extract the key cached by [`peek`][Self::peek] and
+    /// [`pop`][Self::pop].
+    ///
+    /// ```rust
+    /// # use lifetime_abstractions::*;
+    /// struct Ints {
+    ///     v: Vec<i32>,
+    ///     idx: usize,
+    /// }
+    ///
+    /// impl StreamingIterator for Ints {
+    ///     type Item = Lt!(for<'a> &'a i32);
+    ///
+    ///     fn next<'a>(&'a mut self) -> Option<&'a i32> {
+    ///         let item = self.v.get(self.idx);
+    ///         if item.is_some() {
+    ///             self.idx += 1;
+    ///         }
+    ///         item
+    ///     }
+    /// }
+    ///
+    /// let mut p = Peekable::new(Ints { v: vec![1, 2, 3], idx: 0 }, |x: &&i32| **x);
+    /// assert_eq!(p.peek(), Some(&1));
+    /// assert_eq!(p.peek(), Some(&1)); // peeking again does not advance
+    /// assert_eq!(p.pop(), Some(1));
+    /// assert_eq!(p.pop(), Some(2));
+    /// assert_eq!(p.peek(), Some(&3));
+    /// assert_eq!(p.pop(), Some(3));
+    /// assert_eq!(p.pop(), None);
+    /// ```
+    pub fn new(s: S, f: F) -> Self {
+        Peekable { s, f, cache: None }
+    }
+
+    fn fill(&mut self) -> &mut Option<K> {
+        let f = &mut self.f;
+        let s = &mut self.s;
+        self.cache.get_or_insert_with(|| s.next().as_ref().map(f))
+    }
+
+    /// Returns the key of the upcoming item, without consuming it.
+    pub fn peek(&mut self) -> Option<&K> {
+        self.fill().as_ref()
+    }
+
+    /// Consumes and returns the key of the upcoming item.
+    pub fn pop(&mut self) -> Option<K> {
+        self.fill();
+        self.cache.take().unwrap()
+    }
+}
+
+/// An entry in the binary heap used by [`union`], ordered for a min-heap by reversing `K`'s
+/// ordering (the standard library's [`BinaryHeap`] is a max-heap).
+struct HeapEntry<K> {
+    key: K,
+    idx: usize,
+}
+
+impl<K: PartialEq> PartialEq for HeapEntry<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for HeapEntry<K> {}
+
+impl<K: Ord> PartialOrd for HeapEntry<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> Ord for HeapEntry<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key)
+    }
+}
+
+/// An [`Iterator`] yielding the sorted union of the keys of several streams, as created by
+/// [`union`].
+pub struct Union<Item, K, F> {
+    streams: Vec<Peekable<Box<dyn StreamingIterator<Item = Item>>, K, F>>,
+    heap: BinaryHeap<HeapEntry<K>>,
+    started: bool,
+}
+
+/// Merges `streams`, all sharing the item abstraction `Item`, into the sorted union of the keys
+/// extracted from their items by `f`.
+///
+/// `streams` must each already yield items in ascending order of their extracted key, and `f` must
+/// be a stable key extractor shared by every stream. Duplicate keys, whether repeated within a
+/// single stream or occurring across several streams, are only yielded once.
+///
+/// ```rust
+/// # use lifetime_abstractions::*;
+/// struct VecStream {
+///     v: Vec<i32>,
+///     idx: usize,
+/// }
+///
+/// impl StreamingIterator for VecStream {
+///     type Item = Lt!(for<'a> &'a i32);
+///
+///     fn next<'a>(&'a mut self) -> Option<&'a i32> {
+///         let item = self.v.get(self.idx);
+///         if item.is_some() {
+///             self.idx += 1;
+///         }
+///         item
+///     }
+/// }
+///
+/// fn stream(v: Vec<i32>) -> Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>> {
+///     Box::new(VecStream { v, idx: 0 })
+/// }
+///
+/// // Duplicate keys, both within a stream and across streams, are collapsed.
+/// let merged: Vec<i32> = union(
+///     vec![stream(vec![1, 1, 3, 5]), stream(vec![2, 3, 4]), stream(vec![3, 6])],
+///     |x: &&i32| **x,
+/// )
+/// .collect();
+/// assert_eq!(merged, vec![1, 2, 3, 4, 5, 6]);
+///
+/// // An empty list of streams yields an empty union.
+/// let no_streams: Vec<Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>>> = Vec::new();
+/// let empty: Vec<i32> = union(no_streams, |x: &&i32| **x).collect();
+/// assert_eq!(empty, Vec::<i32>::new());
+/// ```
+pub fn union<Item, K, F>(
+    streams: Vec<Box<dyn StreamingIterator<Item = Item>>>,
+    f: F,
+) -> Union<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord,
+    F: Clone + for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    Union {
+        streams: streams
+            .into_iter()
+            .map(|s| Peekable::new(s, f.clone()))
+            .collect(),
+        heap: BinaryHeap::new(),
+        started: false,
+    }
+}
+
+impl<Item, K, F> Union<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord,
+    F: for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    fn refill(&mut self, idx: usize) {
+        if let Some(key) = self.streams[idx].pop() {
+            self.heap.push(HeapEntry { key, idx });
+        }
+    }
+}
+
+impl<Item, K, F> Iterator for Union<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord,
+    F: for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        if !self.started {
+            self.started = true;
+            for idx in 0..self.streams.len() {
+                self.refill(idx);
+            }
+        }
+        let HeapEntry { key, idx } = self.heap.pop()?;
+        self.refill(idx);
+        while let Some(top) = self.heap.peek() {
+            if top.key == key {
+                let dup = self.heap.pop().unwrap();
+                self.refill(dup.idx);
+            } else {
+                break;
+            }
+        }
+        Some(key)
+    }
+}
+
+/// An [`Iterator`] yielding the sorted intersection of the keys of several streams, as created by
+/// [`intersection`].
+pub struct Intersection<Item, K, F> {
+    streams: Vec<Peekable<Box<dyn StreamingIterator<Item = Item>>, K, F>>,
+}
+
+/// Merges `streams`, all sharing the item abstraction `Item`, into the sorted intersection of the
+/// keys extracted from their items by `f`, using a leapfrog join.
+///
+/// `streams` must each already yield items in ascending order of their extracted key, and `f` must
+/// be a stable key extractor shared by every stream. An empty `streams` yields an empty
+/// intersection, matching the usual convention for the intersection of zero sets.
+///
+/// ```rust
+/// # use lifetime_abstractions::*;
+/// struct VecStream {
+///     v: Vec<i32>,
+///     idx: usize,
+/// }
+///
+/// impl StreamingIterator for VecStream {
+///     type Item = Lt!(for<'a> &'a i32);
+///
+///     fn next<'a>(&'a mut self) -> Option<&'a i32> {
+///         let item = self.v.get(self.idx);
+///         if item.is_some() {
+///             self.idx += 1;
+///         }
+///         item
+///     }
+/// }
+///
+/// fn stream(v: Vec<i32>) -> Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>> {
+///     Box::new(VecStream { v, idx: 0 })
+/// }
+///
+/// // Duplicate keys within a stream are collapsed in the result, including when the leapfrog
+/// // join doesn't skip past them (misaligned duplicates that get walked past on the way to the
+/// // next candidate, and duplicates aligned across every stream at the matching key itself).
+/// let common: Vec<i32> = intersection(
+///     vec![stream(vec![1, 2, 2, 3, 4]), stream(vec![2, 3, 3, 4, 5]), stream(vec![0, 2, 3, 4, 9])],
+///     |x: &&i32| **x,
+/// )
+/// .collect();
+/// assert_eq!(common, vec![2, 3, 4]);
+///
+/// let aligned: Vec<i32> = intersection(vec![stream(vec![1, 1, 3])], |x: &&i32| **x).collect();
+/// assert_eq!(aligned, vec![1, 3]);
+///
+/// let both_aligned: Vec<i32> = intersection(
+///     vec![stream(vec![1, 1, 3]), stream(vec![1, 1, 3])],
+///     |x: &&i32| **x,
+/// )
+/// .collect();
+/// assert_eq!(both_aligned, vec![1, 3]);
+///
+/// // The intersection of zero sets is empty.
+/// let no_streams: Vec<Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>>> = Vec::new();
+/// let empty: Vec<i32> = intersection(no_streams, |x: &&i32| **x).collect();
+/// assert_eq!(empty, Vec::<i32>::new());
+/// ```
+pub fn intersection<Item, K, F>(
+    streams: Vec<Box<dyn StreamingIterator<Item = Item>>>,
+    f: F,
+) -> Intersection<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord + Clone,
+    F: Clone + for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    Intersection {
+        streams: streams
+            .into_iter()
+            .map(|s| Peekable::new(s, f.clone()))
+            .collect(),
+    }
+}
+
+impl<Item, K, F> Iterator for Intersection<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord + Clone,
+    F: for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        if self.streams.is_empty() {
+            return None;
+        }
+        loop {
+            let mut max_key: Option<K> = None;
+            for s in &mut self.streams {
+                let key = s.peek()?.clone();
+                max_key = Some(match max_key {
+                    Some(cur) if cur >= key => cur,
+                    _ => key,
+                });
+            }
+            let max_key = max_key.unwrap();
+            let all_equal = self.streams.iter_mut().all(|s| s.peek() == Some(&max_key));
+            if all_equal {
+                for s in &mut self.streams {
+                    s.pop();
+                    while s.peek() == Some(&max_key) {
+                        s.pop();
+                    }
+                }
+                return Some(max_key);
+            }
+            for s in &mut self.streams {
+                if s.peek() != Some(&max_key) {
+                    s.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An [`Iterator`] yielding the sorted difference of the keys of `first` minus the keys of
+/// `rest`, as created by [`difference`].
+pub struct Difference<Item, K, F> {
+    first: Peekable<Box<dyn StreamingIterator<Item = Item>>, K, F>,
+    rest: Vec<Peekable<Box<dyn StreamingIterator<Item = Item>>, K, F>>,
+}
+
+/// Yields the keys extracted from `first`'s items, in order, skipping any key that also appears
+/// as a key of one of the `rest` streams.
+///
+/// `first` and every stream in `rest` must already yield items in ascending order of the key
+/// extracted by `f`, which must be a stable key extractor shared by every stream. As with
+/// [`union`] and [`intersection`], duplicate keys within a single stream are collapsed: each
+/// distinct key is yielded at most once.
+///
+/// ```rust
+/// # use lifetime_abstractions::*;
+/// struct VecStream {
+///     v: Vec<i32>,
+///     idx: usize,
+/// }
+///
+/// impl StreamingIterator for VecStream {
+///     type Item = Lt!(for<'a> &'a i32);
+///
+///     fn next<'a>(&'a mut self) -> Option<&'a i32> {
+///         let item = self.v.get(self.idx);
+///         if item.is_some() {
+///             self.idx += 1;
+///         }
+///         item
+///     }
+/// }
+///
+/// fn stream(v: Vec<i32>) -> Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>> {
+///     Box::new(VecStream { v, idx: 0 })
+/// }
+///
+/// // Duplicate keys in `first` are collapsed, and multiple `rest` streams are all consulted.
+/// let only_in_first: Vec<i32> = difference(
+///     stream(vec![1, 1, 2, 3, 3, 4, 5]),
+///     vec![stream(vec![2, 4]), stream(vec![5])],
+///     |x: &&i32| **x,
+/// )
+/// .collect();
+/// assert_eq!(only_in_first, vec![1, 3]);
+///
+/// // With no `rest` streams, every distinct key of `first` is yielded.
+/// let no_rest: Vec<Box<dyn StreamingIterator<Item = Lt!(for<'a> &'a i32)>>> = Vec::new();
+/// let all: Vec<i32> = difference(stream(vec![1, 1, 2]), no_rest, |x: &&i32| **x).collect();
+/// assert_eq!(all, vec![1, 2]);
+/// ```
+pub fn difference<Item, K, F>(
+    first: Box<dyn StreamingIterator<Item = Item>>,
+    rest: Vec<Box<dyn StreamingIterator<Item = Item>>>,
+    f: F,
+) -> Difference<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord + Clone,
+    F: Clone + for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    Difference {
+        first: Peekable::new(first, f.clone()),
+        rest: rest
+            .into_iter()
+            .map(|s| Peekable::new(s, f.clone()))
+            .collect(),
+    }
+}
+
+impl<Item, K, F> Iterator for Difference<Item, K, F>
+where
+    Item: LtAbs,
+    K: Ord + Clone,
+    F: for<'a> FnMut(&LtApply<'a, Item>) -> K,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<K> {
+        loop {
+            let key = self.first.peek()?.clone();
+            let mut found_in_rest = false;
+            for s in &mut self.rest {
+                loop {
+                    match s.peek() {
+                        Some(k) if *k < key => {
+                            s.pop();
+                        }
+                        Some(k) if *k == key => {
+                            found_in_rest = true;
+                            break;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            self.first.pop();
+            while self.first.peek() == Some(&key) {
+                self.first.pop();
+            }
+            if !found_in_rest {
+                return Some(key);
+            }
+        }
+    }
+}
+
+/// Gives a closure a fully higher-ranked bound against the chosen item abstractions `A` and `B`,
+/// i.e. `for<'a> FnMut(Lt<'a>, LtApply<'a, A>) -> LtApply<'a, B>`, instead of letting type
+/// inference settle on a single concrete lifetime for it.
+///
+/// This is just the identity function, but stating its argument's bound this way gives type
+/// inference an explicit, fully generic expected type to check the closure literal against, which
+/// is what [makes inference succeed for this kind of closure][constrain-closure]. As with
+/// [`StreamingIterator::map`], `f` takes an ignored [`Lt<'a>`][struct@Lt] as its first argument, so
+/// that the placeholder lifetime is "constrained" by an argument type instead of appearing only in
+/// the return type.
+///
+/// [`lt_closure!`] wraps this to avoid spelling out `A` and `B` via turbofish at every call site.
+///
+/// [constrain-closure]:https://stackoverflow.com/a/46198877
+pub fn constrain_lt_fn<A, B, F>(f: F) -> F
+where
+    A: LtAbs,
+    B: LtAbs,
+    F: for<'a> FnMut(Lt<'a>, LtApply<'a, A>) -> LtApply<'a, B>,
+{
+    f
+}
+
+/// Constrains a closure literal against the item abstractions `$a` and `$b` using
+/// [`constrain_lt_fn`], without having to name them via turbofish.
+///
+/// `$closure` still needs to take the leading [`Lt<'a>`][struct@Lt] argument [`constrain_lt_fn`]
+/// requires; this macro only fills in its two type parameters.
+///
+/// ```rust
+/// # use lifetime_abstractions::*;
+/// type Num = Lt!(for<'a> usize);
+/// type Doubled = Lt!(for<'a> usize);
+///
+/// struct Count(usize);
+///
+/// impl StreamingIterator for Count {
+///     type Item = Num;
+///
+///     fn next<'a>(&'a mut self) -> Option<usize> {
+///         if self.0 == 0 {
+///             return None;
+///         }
+///         self.0 -= 1;
+///         Some(self.0)
+///     }
+/// }
+///
+/// // The turbofish on `map` is still needed: `lt_closure!` only fixes the closure's own bound, it
+/// // cannot also tell `map` what its independent `OutItem` parameter should be.
+/// let doubled: Vec<usize> = Count(3)
+///     .map::<Doubled, _>(lt_closure!(Num => Doubled, |_lt, n| n * 2))
+///     .to_iter()
+///     .collect();
+/// assert_eq!(doubled, vec![4, 2, 0]);
+/// ```
+#[macro_export]
+macro_rules! lt_closure {
+    ($a:ty => $b:ty, $closure:expr) => {
+        $crate::constrain_lt_fn::<$a, $b, _>($closure)
+    };
+}